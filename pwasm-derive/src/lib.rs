@@ -0,0 +1,179 @@
+//! `#[wasm_host_module]`: generate the `UserFunctionDescriptor` table and
+//! `UserFunctionExecutor` dispatch for a native module from a plain Rust `impl` block,
+//! so that writing a native module no longer means hand-maintaining descriptors and a
+//! `match name` dispatch alongside the real Rust method signatures.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+extern crate syn;
+#[macro_use]
+extern crate quote;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{FnArg, ImplItem, ItemImpl, ReturnType, Type};
+
+#[proc_macro_attribute]
+pub fn wasm_host_module(_attr: TokenStream, item: TokenStream) -> TokenStream {
+	let input: ItemImpl = syn::parse(item)
+		.expect("#[wasm_host_module] can only be applied to an impl block");
+	let self_ty = &input.self_ty;
+
+	let mut descriptors = Vec::new();
+	let mut dispatch_arms = Vec::new();
+
+	for impl_item in &input.items {
+		let method = match *impl_item {
+			ImplItem::Method(ref method) => method,
+			_ => continue,
+		};
+
+		let name = method.sig.ident.to_string();
+		let method_ident = &method.sig.ident;
+
+		let mut value_types = Vec::new();
+		let mut fetch_args = Vec::new();
+		let mut arg_index = 0usize;
+		for input in method.sig.decl.inputs.iter() {
+			let ty = match *input {
+				FnArg::SelfRef(_) | FnArg::SelfValue(_) => continue,
+				FnArg::Captured(ref captured) => &captured.ty,
+				_ => panic!("Unsupported argument pattern in native function {}", name),
+			};
+
+			value_types.push(value_type_of(ty));
+			fetch_args.push(quote! { args.nth::<#ty>(#arg_index)? });
+			arg_index += 1;
+		}
+
+		let return_value_type = match method.sig.decl.output {
+			ReturnType::Default => quote! { None },
+			ReturnType::Type(_, ref ty) => {
+				let value_type = value_type_of(ty);
+				quote! { Some(#value_type) }
+			},
+		};
+
+		descriptors.push(quote! {
+			::parity_wasm::interpreter::UserFunctionDescriptor::statik(
+				#name,
+				&[#(#value_types),*],
+				#return_value_type,
+			)
+		});
+
+		let call = quote! { self.#method_ident(#(#fetch_args),*) };
+		let body = match method.sig.decl.output {
+			ReturnType::Default => quote! { #call; Ok(None) },
+			ReturnType::Type(_, ref ty) => {
+				let wrapped = wrap_result(ty, quote! { result });
+				quote! {
+					let result = #call;
+					Ok(#wrapped)
+				}
+			},
+		};
+
+		dispatch_arms.push(quote! {
+			#name => { #body }
+		});
+	}
+
+	let expanded = quote! {
+		#input
+
+		impl #self_ty {
+			/// Native function descriptors generated from this impl block by `#[wasm_host_module]`.
+			pub fn descriptors() -> ::std::borrow::Cow<'static, [::parity_wasm::interpreter::UserFunctionDescriptor]> {
+				::std::borrow::Cow::from(vec![#(#descriptors),*])
+			}
+		}
+
+		impl ::parity_wasm::interpreter::UserFunctionExecutor for #self_ty {
+			fn execute(
+				&mut self,
+				name: &str,
+				_context: &mut ::parity_wasm::interpreter::CallerContext,
+				args: ::parity_wasm::interpreter::RuntimeArgs,
+			) -> Result<Option<::parity_wasm::interpreter::RuntimeValue>, ::parity_wasm::interpreter::Error> {
+				match name {
+					#(#dispatch_arms,)*
+					_ => Err(::parity_wasm::interpreter::Error::Native(format!("unknown native function {}", name))),
+				}
+			}
+		}
+	};
+
+	expanded.into()
+}
+
+fn value_type_of(ty: &Type) -> TokenStream2 {
+	match quote!(#ty).to_string().as_str() {
+		"i32" => quote! { ::parity_wasm::elements::ValueType::I32 },
+		"i64" => quote! { ::parity_wasm::elements::ValueType::I64 },
+		"f32" => quote! { ::parity_wasm::elements::ValueType::F32 },
+		"f64" => quote! { ::parity_wasm::elements::ValueType::F64 },
+		other => panic!("Unsupported native function argument/return type: {}", other),
+	}
+}
+
+fn wrap_result(ty: &Type, expr: TokenStream2) -> TokenStream2 {
+	match quote!(#ty).to_string().as_str() {
+		"i32" => quote! { Some(::parity_wasm::interpreter::RuntimeValue::I32(#expr)) },
+		"i64" => quote! { Some(::parity_wasm::interpreter::RuntimeValue::I64(#expr)) },
+		"f32" => quote! { Some(::parity_wasm::interpreter::RuntimeValue::F32(#expr)) },
+		"f64" => quote! { Some(::parity_wasm::interpreter::RuntimeValue::F64(#expr)) },
+		other => panic!("Unsupported native function argument/return type: {}", other),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{value_type_of, wrap_result};
+	use syn::Type;
+
+	fn parse_type(source: &str) -> Type {
+		syn::parse_str(source).expect("valid Rust type")
+	}
+
+	#[test]
+	fn value_type_of_maps_each_supported_type() {
+		assert_eq!(value_type_of(&parse_type("i32")).to_string(), quote! { ::parity_wasm::elements::ValueType::I32 }.to_string());
+		assert_eq!(value_type_of(&parse_type("i64")).to_string(), quote! { ::parity_wasm::elements::ValueType::I64 }.to_string());
+		assert_eq!(value_type_of(&parse_type("f32")).to_string(), quote! { ::parity_wasm::elements::ValueType::F32 }.to_string());
+		assert_eq!(value_type_of(&parse_type("f64")).to_string(), quote! { ::parity_wasm::elements::ValueType::F64 }.to_string());
+	}
+
+	#[test]
+	#[should_panic(expected = "Unsupported native function argument/return type")]
+	fn value_type_of_panics_on_unsupported_type() {
+		value_type_of(&parse_type("bool"));
+	}
+
+	#[test]
+	fn wrap_result_wraps_each_supported_type_in_the_matching_runtime_value_variant() {
+		let expr = quote! { result };
+		assert_eq!(
+			wrap_result(&parse_type("i32"), expr.clone()).to_string(),
+			quote! { Some(::parity_wasm::interpreter::RuntimeValue::I32(result)) }.to_string(),
+		);
+		assert_eq!(
+			wrap_result(&parse_type("i64"), expr.clone()).to_string(),
+			quote! { Some(::parity_wasm::interpreter::RuntimeValue::I64(result)) }.to_string(),
+		);
+		assert_eq!(
+			wrap_result(&parse_type("f32"), expr.clone()).to_string(),
+			quote! { Some(::parity_wasm::interpreter::RuntimeValue::F32(result)) }.to_string(),
+		);
+		assert_eq!(
+			wrap_result(&parse_type("f64"), expr).to_string(),
+			quote! { Some(::parity_wasm::interpreter::RuntimeValue::F64(result)) }.to_string(),
+		);
+	}
+
+	#[test]
+	#[should_panic(expected = "Unsupported native function argument/return type")]
+	fn wrap_result_panics_on_unsupported_type() {
+		wrap_result(&parse_type("bool"), quote! { result });
+	}
+}
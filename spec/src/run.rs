@@ -5,22 +5,32 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::fs::File;
 use std::sync::Arc;
+use std::collections::HashMap;
 
 use serde_json;
-use test;
+mod test;
 use parity_wasm;
 use parity_wasm::interpreter::{
-    ProgramInstance, ModuleInstance, ModuleInstanceInterface, 
+    ProgramInstance, ModuleInstance, ModuleInstanceInterface, ItemIndex, ExportEntryType,
     Error as InterpreterError,
 };
+use parity_wasm::elements::Internal;
 
-fn setup_program(base_dir: &str, test_module_path: &str) -> (ProgramInstance, Arc<ModuleInstance>) {
+type Registry = HashMap<String, Arc<ModuleInstance>>;
+
+fn try_deserialize(base_dir: &str, test_module_path: &str) -> Result<parity_wasm::elements::Module, parity_wasm::elements::Error> {
+    let mut wasm_path = PathBuf::from(base_dir.clone());
+    wasm_path.push(test_module_path);
+    parity_wasm::deserialize_file(&wasm_path)
+}
+
+fn setup_program(base_dir: &str, test_module_path: &str, registry: &Registry) -> (ProgramInstance, Arc<ModuleInstance>) {
     let mut wasm_path = PathBuf::from(base_dir.clone());
     wasm_path.push(test_module_path);
     let module = parity_wasm::deserialize_file(&wasm_path)
         .expect(&format!("Wasm file {} failed to load", wasm_path.to_string_lossy()));
 	let program = ProgramInstance::new().expect("Failed creating program");
-	let module_instance = program.add_module("test", module).expect("Failed adding module");
+	let module_instance = program.add_module("test", module, Some(registry)).expect("Failed adding module");
     (program, module_instance)
 }
 
@@ -36,11 +46,11 @@ fn runtime_value(test_val: &test::RuntimeValue) -> parity_wasm::RuntimeValue {
         },
         "f32" => {
             let unsigned: u32 = test_val.value.parse().expect("Literal parse error");
-            parity_wasm::RuntimeValue::decode_f32(unsigned)            
+            parity_wasm::RuntimeValue::decode_f32(unsigned)
         },
         "f64" => {
             let unsigned: u64 = test_val.value.parse().expect("Literal parse error");
-            parity_wasm::RuntimeValue::decode_f64(unsigned)            
+            parity_wasm::RuntimeValue::decode_f64(unsigned)
         },
         _ => panic!("Unknwon runtime value type"),
     }
@@ -50,13 +60,103 @@ fn runtime_values(test_vals: &[test::RuntimeValue]) -> Vec<parity_wasm::RuntimeV
     test_vals.iter().map(runtime_value).collect::<Vec<parity_wasm::RuntimeValue>>()
 }
 
-fn run_action(module: &ModuleInstance, action: &test::Action) 
-    -> Result<Option<parity_wasm::RuntimeValue>, InterpreterError> 
+/// An expected `assert_return` value, which might pin down an exact bit pattern or
+/// only a class of NaN (the testsuite uses `nan:canonical`/`nan:arithmetic` tokens
+/// for floats where any bit pattern within that class is an acceptable result).
+#[derive(Debug)]
+enum ExpectedValue {
+    Exact(parity_wasm::RuntimeValue),
+    CanonicalNan32,
+    ArithmeticNan32,
+    CanonicalNan64,
+    ArithmeticNan64,
+}
+
+fn expected_value(test_val: &test::RuntimeValue) -> ExpectedValue {
+    match (test_val.value_type.as_ref(), test_val.value.as_ref()) {
+        ("f32", "nan:canonical") => ExpectedValue::CanonicalNan32,
+        ("f32", "nan:arithmetic") => ExpectedValue::ArithmeticNan32,
+        ("f64", "nan:canonical") => ExpectedValue::CanonicalNan64,
+        ("f64", "nan:arithmetic") => ExpectedValue::ArithmeticNan64,
+        _ => ExpectedValue::Exact(runtime_value(test_val)),
+    }
+}
+
+fn expected_values(test_vals: &[test::RuntimeValue]) -> Vec<ExpectedValue> {
+    test_vals.iter().map(expected_value).collect::<Vec<ExpectedValue>>()
+}
+
+fn is_canonical_nan32(bits: u32) -> bool { bits & 0x7fffffff == 0x7fc00000 }
+fn is_arithmetic_nan32(bits: u32) -> bool { bits & 0x7fc00000 == 0x7fc00000 }
+fn is_canonical_nan64(bits: u64) -> bool { bits & 0x7fffffffffffffff == 0x7ff8000000000000 }
+fn is_arithmetic_nan64(bits: u64) -> bool { bits & 0x7ff8000000000000 == 0x7ff8000000000000 }
+
+fn value_matches(actual: &parity_wasm::RuntimeValue, expected: &ExpectedValue) -> bool {
+    match (actual, expected) {
+        (&parity_wasm::RuntimeValue::F32(actual), &ExpectedValue::CanonicalNan32) => is_canonical_nan32(actual.to_bits()),
+        (&parity_wasm::RuntimeValue::F32(actual), &ExpectedValue::ArithmeticNan32) => is_arithmetic_nan32(actual.to_bits()),
+        (&parity_wasm::RuntimeValue::F64(actual), &ExpectedValue::CanonicalNan64) => is_canonical_nan64(actual.to_bits()),
+        (&parity_wasm::RuntimeValue::F64(actual), &ExpectedValue::ArithmeticNan64) => is_arithmetic_nan64(actual.to_bits()),
+        (actual, &ExpectedValue::Exact(ref expected)) => actual == expected,
+        _ => false,
+    }
+}
+
+fn run_action(module: &ModuleInstance, action: &test::Action)
+    -> Result<Option<parity_wasm::RuntimeValue>, InterpreterError>
 {
     match *action {
         test::Action::Invoke { ref field, ref args} => {
             module.execute_export(field, runtime_values(args).into())
-        }
+        },
+        test::Action::Get { ref field } => {
+            match module.export_entry(field, &ExportEntryType::Any)? {
+                Internal::Global(index) => {
+                    let global = module.global(ItemIndex::Internal(index), None)?;
+                    Ok(Some(global.get()))
+                },
+                _ => Err(InterpreterError::Global(format!("{} is not a global export", field))),
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod nan_classification_tests {
+    use super::{is_canonical_nan32, is_arithmetic_nan32, is_canonical_nan64, is_arithmetic_nan64};
+
+    #[test]
+    fn canonical_nan32_matches_only_canonical_bit_pattern() {
+        assert!(is_canonical_nan32(0x7fc00000));
+        assert!(is_canonical_nan32(0xffc00000)); // sign bit is ignored
+        assert!(!is_canonical_nan32(0x7fc00001)); // arithmetic, but not canonical
+        assert!(!is_canonical_nan32(0x7f800000)); // +infinity, not a NaN at all
+    }
+
+    #[test]
+    fn arithmetic_nan32_matches_any_nan_with_the_top_mantissa_bit_set() {
+        assert!(is_arithmetic_nan32(0x7fc00000)); // canonical NaN is also arithmetic
+        assert!(is_arithmetic_nan32(0x7fc00001));
+        assert!(is_arithmetic_nan32(0xffc0dead));
+        assert!(!is_arithmetic_nan32(0x7f800001)); // signaling NaN, top mantissa bit clear
+        assert!(!is_arithmetic_nan32(0x7f800000)); // +infinity, not a NaN at all
+    }
+
+    #[test]
+    fn canonical_nan64_matches_only_canonical_bit_pattern() {
+        assert!(is_canonical_nan64(0x7ff8000000000000));
+        assert!(is_canonical_nan64(0xfff8000000000000)); // sign bit is ignored
+        assert!(!is_canonical_nan64(0x7ff8000000000001)); // arithmetic, but not canonical
+        assert!(!is_canonical_nan64(0x7ff0000000000000)); // +infinity, not a NaN at all
+    }
+
+    #[test]
+    fn arithmetic_nan64_matches_any_nan_with_the_top_mantissa_bit_set() {
+        assert!(is_arithmetic_nan64(0x7ff8000000000000)); // canonical NaN is also arithmetic
+        assert!(is_arithmetic_nan64(0x7ff8000000000001));
+        assert!(is_arithmetic_nan64(0xfff8deaddeaddead));
+        assert!(!is_arithmetic_nan64(0x7ff0000000000001)); // signaling NaN, top mantissa bit clear
+        assert!(!is_arithmetic_nan64(0x7ff0000000000000)); // +infinity, not a NaN at all
     }
 }
 
@@ -84,10 +184,21 @@ pub fn spec(name: &str) {
         .expect(&format!("Failed to load json file {}", &json_spec_path.to_string_lossy()));
     let spec: test::Spec = serde_json::from_reader(&mut f).expect("Failed to deserialize JSON file");
 
+    // Modules registered via `register` commands, keyed by the name they were registered
+    // under, so that later modules can import from them by that name.
+    let mut registry: Registry = HashMap::new();
+    // Every module that was given an inline identifier (`(module $M1 ...)`), keyed by
+    // that identifier, so `register` can look up a module other than the current one.
+    let mut named_modules: Registry = HashMap::new();
+
     let first_command = &spec.commands[0];
     let (mut _program, mut module) = match first_command {
-        &test::Command::Module { ref filename, .. } => {
-            setup_program(&outdir, filename)
+        &test::Command::Module { ref name, ref filename, .. } => {
+            let (program, instance) = setup_program(&outdir, filename, &registry);
+            if let Some(ref name) = *name {
+                named_modules.insert(name.clone(), instance.clone());
+            }
+            (program, instance)
         },
         _ => {
             panic!("First command supposed to specify module");
@@ -97,17 +208,39 @@ pub fn spec(name: &str) {
     for command in spec.commands.iter().skip(1) {
         println!("command {:?}", command);
         match command {
-            &test::Command::Module { ref filename, .. } => {
-                let (_new_program, new_module) = setup_program(&outdir, &filename);
+            &test::Command::Module { ref name, ref filename, .. } => {
+                let (_new_program, new_module) = setup_program(&outdir, &filename, &registry);
+                if let Some(ref name) = *name {
+                    named_modules.insert(name.clone(), new_module.clone());
+                }
                 module = new_module;
             },
+            &test::Command::Register { ref name, ref as_name, .. } => {
+                let target = match *name {
+                    Some(ref name) => named_modules.get(name)
+                        .unwrap_or_else(|| panic!("register: no previously loaded module named {}", name))
+                        .clone(),
+                    None => module.clone(),
+                };
+                registry.insert(as_name.clone(), target);
+                println!("registered module {} as {}", name.as_ref().map(|n| n.as_str()).unwrap_or("<current>"), as_name);
+            },
+            &test::Command::Action { line, ref action } => {
+                match run_action(&*module, action) {
+                    Ok(_) => println!("action at line {} - success", line),
+                    Err(e) => panic!("Expected action at line {} to succeed, got error: {:?}", line, e),
+                }
+            },
             &test::Command::AssertReturn { line, ref action, ref expected } => {
                 let result = run_action(&*module, action);
                 match result {
                     Ok(result) => {
-                        let spec_expected = runtime_values(expected);
+                        let spec_expected = expected_values(expected);
                         let actual_result = result.into_iter().collect::<Vec<parity_wasm::RuntimeValue>>();
-                        assert_eq!(actual_result, spec_expected);
+                        assert_eq!(actual_result.len(), spec_expected.len(), "assert_return at line {}: arity mismatch", line);
+                        for (actual, expected) in actual_result.iter().zip(spec_expected.iter()) {
+                            assert!(value_matches(actual, expected), "assert_return at line {}: expected {:?}, got {:?}", line, expected, actual);
+                        }
                         println!("assert_return at line {} - success", line);
                     },
                     Err(e) => {
@@ -122,10 +255,50 @@ pub fn spec(name: &str) {
                         panic!("Expected action to result in a trap, got result: {:?}", result);
                     },
                     Err(e) => {
-                        println!("assert_trap at line {} - success ({:?})", line, e);                    
+                        println!("assert_trap at line {} - success ({:?})", line, e);
                     }
                 }
-            }
+            },
+            &test::Command::AssertExhaustion { line, ref action, .. } => {
+                let result = run_action(&*module, action);
+                match result {
+                    Ok(result) => {
+                        panic!("Expected action to exhaust the stack, got result: {:?}", result);
+                    },
+                    Err(e) => {
+                        println!("assert_exhaustion at line {} - success ({:?})", line, e);
+                    }
+                }
+            },
+            &test::Command::AssertMalformed { line, ref filename, ref text } => {
+                match try_deserialize(&outdir, filename) {
+                    Ok(_) => panic!("Expected module {} to be malformed at line {}, but it deserialized successfully", filename, line),
+                    Err(e) => println!("assert_malformed at line {} - success ({:?}, expected: {})", line, e, text),
+                }
+            },
+            &test::Command::AssertInvalid { line, ref filename, ref text } => {
+                let outcome = try_deserialize(&outdir, filename)
+                    .map_err(|e| format!("{:?}", e))
+                    .and_then(|deserialized| {
+                        let program = ProgramInstance::new().expect("Failed creating program");
+                        program.add_module("test", deserialized, Some(&registry))
+                            .map(|_| ())
+                            .map_err(|e| format!("{:?}", e))
+                    });
+                match outcome {
+                    Ok(_) => panic!("Expected module {} to be invalid at line {}, but it validated successfully", filename, line),
+                    Err(e) => println!("assert_invalid at line {} - success ({}, expected: {})", line, e, text),
+                }
+            },
+            &test::Command::AssertUnlinkable { line, ref filename, ref text } => {
+                let deserialized = try_deserialize(&outdir, filename)
+                    .expect(&format!("Wasm file {} failed to load", filename));
+                let program = ProgramInstance::new().expect("Failed creating program");
+                match program.add_module("test", deserialized, Some(&registry)) {
+                    Ok(_) => panic!("Expected module {} to be unlinkable at line {}, but it linked successfully", filename, line),
+                    Err(e) => println!("assert_unlinkable at line {} - success ({:?}, expected: {})", line, e, text),
+                }
+            },
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,81 @@
+//! The JSON schema `wast2wasm --spec` emits for a `.wast` testsuite file: one
+//! `Spec` per file, holding the ordered `Command`s the runner in `run.rs` replays.
+//! Field shapes follow the JSON wabt produces, not Rust naming conventions (hence
+//! the `#[serde(rename = "as")]` below), so this is a direct mirror of that format
+//! rather than something we get to redesign.
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct Spec {
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Command {
+    Module {
+        line: u64,
+        name: Option<String>,
+        filename: String,
+    },
+    Register {
+        line: u64,
+        name: Option<String>,
+        #[serde(rename = "as")]
+        as_name: String,
+    },
+    Action {
+        line: u64,
+        action: Action,
+    },
+    AssertReturn {
+        line: u64,
+        action: Action,
+        expected: Vec<RuntimeValue>,
+    },
+    AssertTrap {
+        line: u64,
+        action: Action,
+        text: String,
+    },
+    AssertExhaustion {
+        line: u64,
+        action: Action,
+        text: String,
+    },
+    AssertMalformed {
+        line: u64,
+        filename: String,
+        text: String,
+    },
+    AssertInvalid {
+        line: u64,
+        filename: String,
+        text: String,
+    },
+    AssertUnlinkable {
+        line: u64,
+        filename: String,
+        text: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Invoke {
+        field: String,
+        args: Vec<RuntimeValue>,
+    },
+    Get {
+        field: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RuntimeValue {
+    #[serde(rename = "type")]
+    pub value_type: String,
+    pub value: String,
+}
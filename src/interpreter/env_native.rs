@@ -5,19 +5,165 @@ use parking_lot::RwLock;
 use elements::{Internal, ValueType};
 use interpreter::Error;
 use interpreter::module::{ModuleInstanceInterface, ExecutionParams, ItemIndex,
-	CallerContext, ExportEntryType, InternalFunctionReference, InternalFunction, FunctionSignature};
+	CallerContext, ExportEntryType, InternalFunctionReference, InternalFunction, FunctionSignature,
+	MemoryDescriptor, TableDescriptor, GlobalDescriptor};
 use interpreter::memory::MemoryInstance;
 use interpreter::table::TableInstance;
 use interpreter::value::RuntimeValue;
 use interpreter::variable::{VariableInstance, VariableType};
 
+/// Base composite index shared by every native (function/memory/table/global) index
+/// space. The four kinds never share a single namespace to disambiguate between —
+/// each is only ever looked up through its own kind-specific accessor (`function_type`,
+/// `memory`, `table`, `global`), so one offset below which an index is "not native"
+/// is all any of them needs; there is no reason for them to diverge.
+pub const NATIVE_INDEX_MIN: u32 = 10001;
 /// Min index of native function.
-pub const NATIVE_INDEX_FUNC_MIN: u32 = 10001;
+pub const NATIVE_INDEX_FUNC_MIN: u32 = NATIVE_INDEX_MIN;
+/// Min index of native memory.
+pub const NATIVE_INDEX_MEMORY_MIN: u32 = NATIVE_INDEX_MIN;
+/// Min index of native table.
+pub const NATIVE_INDEX_TABLE_MIN: u32 = NATIVE_INDEX_MIN;
+/// Min index of native global.
+pub const NATIVE_INDEX_GLOBAL_MIN: u32 = NATIVE_INDEX_MIN;
+
+/// Resolves a single host module's imports by field name.
+///
+/// Unlike `UserFunctionExecutor`, which only covers function calls, this covers all
+/// four importable kinds, so an embedder can supply host-provided memories, tables,
+/// and globals the same way it supplies native functions.
+pub trait ImportResolver {
+	/// Resolve a function import, checking it against the module's declared signature.
+	fn resolve_func<'b>(&'b self, field: &str, signature: &FunctionSignature) -> Result<InternalFunctionReference<'b>, Error>;
+	/// Resolve a memory import, checking it against the module's declared descriptor.
+	fn resolve_memory(&self, field: &str, descriptor: &MemoryDescriptor) -> Result<Arc<MemoryInstance>, Error>;
+	/// Resolve a table import, checking it against the module's declared descriptor.
+	fn resolve_table(&self, field: &str, descriptor: &TableDescriptor) -> Result<Arc<TableInstance>, Error>;
+	/// Resolve a global import, checking it against the module's declared descriptor.
+	fn resolve_global(&self, field: &str, descriptor: &GlobalDescriptor) -> Result<Arc<VariableInstance>, Error>;
+}
 
 /// User functions executor.
 pub trait UserFunctionExecutor {
 	/// Execute function with given name.
-	fn execute(&mut self, name: &str, context: CallerContext) -> Result<Option<RuntimeValue>, Error>;
+	///
+	/// `context` is borrowed rather than consumed so that a caller driving a
+	/// resumable call (see `NativeModuleInstance::call_internal_function_resumable`)
+	/// keeps ownership of it across a suspend, instead of losing the caller's
+	/// activation the moment `execute` returns `Error::Suspend`.
+	fn execute(&mut self, name: &str, context: &mut CallerContext, args: RuntimeArgs) -> Result<Option<RuntimeValue>, Error>;
+}
+
+/// Conversion from an opaque `RuntimeValue` to a concrete Rust argument type.
+pub trait FromRuntimeValue: Sized {
+	/// Convert, returning `None` if `value` is not of the expected variant.
+	fn from_runtime_value(value: RuntimeValue) -> Option<Self>;
+}
+
+impl FromRuntimeValue for i32 {
+	fn from_runtime_value(value: RuntimeValue) -> Option<Self> {
+		match value {
+			RuntimeValue::I32(v) => Some(v),
+			_ => None,
+		}
+	}
+}
+
+impl FromRuntimeValue for i64 {
+	fn from_runtime_value(value: RuntimeValue) -> Option<Self> {
+		match value {
+			RuntimeValue::I64(v) => Some(v),
+			_ => None,
+		}
+	}
+}
+
+impl FromRuntimeValue for f32 {
+	fn from_runtime_value(value: RuntimeValue) -> Option<Self> {
+		match value {
+			RuntimeValue::F32(v) => Some(v),
+			_ => None,
+		}
+	}
+}
+
+impl FromRuntimeValue for f64 {
+	fn from_runtime_value(value: RuntimeValue) -> Option<Self> {
+		match value {
+			RuntimeValue::F64(v) => Some(v),
+			_ => None,
+		}
+	}
+}
+
+/// Typed view over the arguments of a native function call.
+///
+/// Built from the callee's declared `UserFunctionDescriptor::params()` and the
+/// caller's value stack, so implementations of `UserFunctionExecutor` can read
+/// arguments by index and expected type instead of popping and converting
+/// `RuntimeValue`s from `CallerContext` by hand.
+pub struct RuntimeArgs {
+	values: Vec<RuntimeValue>,
+}
+
+impl RuntimeArgs {
+	/// Pop `params.len()` arguments off `context`'s value stack, in declaration order.
+	pub fn pop_from(params: &[ValueType], context: &mut CallerContext) -> Result<Self, Error> {
+		let mut values = vec![RuntimeValue::I32(0); params.len()];
+		for value in values.iter_mut().rev() {
+			*value = context.value_stack.pop()?;
+		}
+		Ok(RuntimeArgs { values: values })
+	}
+
+	/// Number of arguments.
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	/// Nth argument, converted to `T`.
+	///
+	/// Returns `Error::Native` if there is no argument at `idx`, or if the argument
+	/// at `idx` is not of the expected `RuntimeValue` variant for `T`.
+	pub fn nth<T: FromRuntimeValue>(&self, idx: usize) -> Result<T, Error> {
+		let value = self.values.get(idx)
+			.cloned()
+			.ok_or_else(|| Error::Native(format!("missing argument {}", idx)))?;
+		T::from_runtime_value(value)
+			.ok_or_else(|| Error::Native(format!("argument {} is of unexpected type", idx)))
+	}
+}
+
+#[cfg(test)]
+mod runtime_args_tests {
+	use super::RuntimeArgs;
+	use interpreter::value::RuntimeValue;
+
+	// `pop_from` itself needs a real `CallerContext` value stack to pop from, so these
+	// cases build `RuntimeArgs` directly from the declaration-order `Vec` it would
+	// hand to `nth` afterwards, and exercise `nth`'s lookup from there.
+	fn args(values: Vec<RuntimeValue>) -> RuntimeArgs {
+		RuntimeArgs { values: values }
+	}
+
+	#[test]
+	fn nth_reads_arguments_in_declaration_order() {
+		let args = args(vec![RuntimeValue::I32(1), RuntimeValue::I32(2)]);
+		assert_eq!(args.nth::<i32>(0).unwrap(), 1);
+		assert_eq!(args.nth::<i32>(1).unwrap(), 2);
+	}
+
+	#[test]
+	fn nth_out_of_range_is_a_native_error() {
+		let args = args(vec![RuntimeValue::I32(1)]);
+		assert!(args.nth::<i32>(1).is_err());
+	}
+
+	#[test]
+	fn nth_wrong_variant_is_a_native_error() {
+		let args = args(vec![RuntimeValue::I64(1)]);
+		assert!(args.nth::<i32>(0).is_err());
+	}
 }
 
 /// User function descriptor
@@ -65,12 +211,31 @@ impl UserFunctionDescriptor {
 	}
 }
 
-/// Set of user-defined functions
-pub struct UserFunctions<'a> {
+/// Host-provided imports for a native module: functions, and now also memories,
+/// tables, and globals, all keyed by the name they're exposed under.
+pub struct HostImports<'a> {
 	/// Functions list.
 	pub functions: Cow<'static, [UserFunctionDescriptor]>,
 	/// Functions executor.
 	pub executor: &'a mut UserFunctionExecutor,
+	/// Host-provided memories, by name.
+	pub memories: HashMap<String, Arc<MemoryInstance>>,
+	/// Host-provided tables, by name.
+	pub tables: HashMap<String, Arc<TableInstance>>,
+	/// Host-provided globals, by name.
+	pub globals: HashMap<String, Arc<VariableInstance>>,
+}
+
+/// Assign a stable numeric index to each value in `map`, returning the values in
+/// index order alongside a name -> index lookup.
+fn index_by_name<T>(map: HashMap<String, Arc<T>>) -> (Vec<Arc<T>>, HashMap<String, u32>) {
+	let mut values = Vec::with_capacity(map.len());
+	let mut by_name = HashMap::with_capacity(map.len());
+	for (index, (name, value)) in map.into_iter().enumerate() {
+		by_name.insert(name, index as u32);
+		values.push(value);
+	}
+	(values, by_name)
 }
 
 /// Native module instance.
@@ -83,16 +248,37 @@ pub struct NativeModuleInstance<'a> {
 	by_name: HashMap<String, u32>,
 	/// User functions list.
 	functions: Cow<'static, [UserFunctionDescriptor]>,
+	/// Host-provided memories, indexed.
+	memories: Vec<Arc<MemoryInstance>>,
+	/// By-name memories index.
+	memories_by_name: HashMap<String, u32>,
+	/// Host-provided tables, indexed.
+	tables: Vec<Arc<TableInstance>>,
+	/// By-name tables index.
+	tables_by_name: HashMap<String, u32>,
+	/// Host-provided globals, indexed.
+	globals: Vec<Arc<VariableInstance>>,
+	/// By-name globals index.
+	globals_by_name: HashMap<String, u32>,
 }
 
 impl<'a> NativeModuleInstance<'a> {
 	/// Create new native module
-	pub fn new(env: Arc<ModuleInstanceInterface>, functions: UserFunctions<'a>) -> Result<Self, Error> {
+	pub fn new(env: Arc<ModuleInstanceInterface>, imports: HostImports<'a>) -> Result<Self, Error> {
+		let (memories, memories_by_name) = index_by_name(imports.memories);
+		let (tables, tables_by_name) = index_by_name(imports.tables);
+		let (globals, globals_by_name) = index_by_name(imports.globals);
 		Ok(NativeModuleInstance {
 			env: env,
-			executor: RwLock::new(functions.executor),
-			by_name: functions.functions.iter().enumerate().map(|(i, f)| (f.name().to_owned(), i as u32)).collect(),
-			functions: functions.functions,
+			executor: RwLock::new(imports.executor),
+			by_name: imports.functions.iter().enumerate().map(|(i, f)| (f.name().to_owned(), i as u32)).collect(),
+			functions: imports.functions,
+			memories: memories,
+			memories_by_name: memories_by_name,
+			tables: tables,
+			tables_by_name: tables_by_name,
+			globals: globals,
+			globals_by_name: globals_by_name,
 		})
 	}
 }
@@ -118,18 +304,63 @@ impl<'a> ModuleInstanceInterface for NativeModuleInstance<'a> {
 			}
 		}
 
+		if let &ExportEntryType::Memory(_) = required_type {
+			if let Some(index) = self.memories_by_name.get(name) {
+				return Ok(Internal::Memory(NATIVE_INDEX_MEMORY_MIN + *index));
+			}
+		}
+
+		if let &ExportEntryType::Table(_) = required_type {
+			if let Some(index) = self.tables_by_name.get(name) {
+				return Ok(Internal::Table(NATIVE_INDEX_TABLE_MIN + *index));
+			}
+		}
+
+		if let &ExportEntryType::Global(_) = required_type {
+			if let Some(index) = self.globals_by_name.get(name) {
+				return Ok(Internal::Global(NATIVE_INDEX_GLOBAL_MIN + *index));
+			}
+		}
+
 		self.env.export_entry(name, required_type)
 	}
 
 	fn table(&self, index: ItemIndex) -> Result<Arc<TableInstance>, Error> {
+		if let ItemIndex::Internal(index) = index {
+			if index >= NATIVE_INDEX_TABLE_MIN {
+				return self.tables
+					.get((index - NATIVE_INDEX_TABLE_MIN) as usize)
+					.cloned()
+					.ok_or_else(|| Error::Native(format!("missing native table with index {}", index)));
+			}
+		}
+
 		self.env.table(index)
 	}
 
 	fn memory(&self, index: ItemIndex) -> Result<Arc<MemoryInstance>, Error> {
+		if let ItemIndex::Internal(index) = index {
+			if index >= NATIVE_INDEX_MEMORY_MIN {
+				return self.memories
+					.get((index - NATIVE_INDEX_MEMORY_MIN) as usize)
+					.cloned()
+					.ok_or_else(|| Error::Native(format!("missing native memory with index {}", index)));
+			}
+		}
+
 		self.env.memory(index)
 	}
 
 	fn global(&self, index: ItemIndex, variable_type: Option<VariableType>) -> Result<Arc<VariableInstance>, Error> {
+		if let ItemIndex::Internal(index) = index {
+			if index >= NATIVE_INDEX_GLOBAL_MIN {
+				return self.globals
+					.get((index - NATIVE_INDEX_GLOBAL_MIN) as usize)
+					.cloned()
+					.ok_or_else(|| Error::Native(format!("missing native global with index {}", index)));
+			}
+		}
+
 		self.env.global(index, variable_type)
 	}
 
@@ -153,6 +384,18 @@ impl<'a> ModuleInstanceInterface for NativeModuleInstance<'a> {
 	}
 
 	fn function_reference<'b>(&self, index: ItemIndex, externals: Option<&'b HashMap<String, Arc<ModuleInstanceInterface + 'b>>>) -> Result<InternalFunctionReference<'b>, Error> {
+		let composite_index = match index {
+			ItemIndex::IndexSpace(index) | ItemIndex::Internal(index) => index,
+			ItemIndex::External(_) => unreachable!("trying to call function, exported by native env module"),
+		};
+
+		if composite_index >= NATIVE_INDEX_FUNC_MIN {
+			return Ok(InternalFunctionReference {
+				module: self,
+				internal_index: composite_index - NATIVE_INDEX_FUNC_MIN,
+			});
+		}
+
 		self.env.function_reference(index, externals)
 	}
 
@@ -164,7 +407,7 @@ impl<'a> ModuleInstanceInterface for NativeModuleInstance<'a> {
 		Ok(None)
 	}
 
-	fn call_internal_function(&self, outer: CallerContext, index: u32) -> Result<Option<RuntimeValue>, Error> {
+	fn call_internal_function(&self, mut outer: CallerContext, index: u32) -> Result<Option<RuntimeValue>, Error> {
 		if index < NATIVE_INDEX_FUNC_MIN {
 			return self.env.call_internal_function(outer, index);
 		}
@@ -172,13 +415,215 @@ impl<'a> ModuleInstanceInterface for NativeModuleInstance<'a> {
 		self.functions
 			.get((index - NATIVE_INDEX_FUNC_MIN) as usize)
 			.ok_or(Error::Native(format!("trying to call native function with index {}", index)))
-			.and_then(|f| self.executor.write().execute(&f.name(), outer))
+			.and_then(|f| {
+				let args = RuntimeArgs::pop_from(f.params(), &mut outer)?;
+				self.executor.write().execute(&f.name(), &mut outer, args)
+			})
+	}
+}
+
+/// Outcome of driving a native call that is allowed to suspend.
+pub enum Resumed<'c> {
+	/// The call ran to completion synchronously.
+	Finished(Option<RuntimeValue>),
+	/// The call suspended; `Resumable::resume` checks the value it is resumed with and
+	/// hands that value back with the suspended `CallerContext`. It does not push the
+	/// value or continue the suspended call.
+	Suspended(Resumable<'c>),
+}
+
+/// A native call suspended by a `UserFunctionExecutor` that returned `Error::Suspend`
+/// instead of a result — e.g. a host function waiting on asynchronous I/O, a
+/// cross-contract call, or a gas refill. Holds on to the suspended call's own
+/// `CallerContext` (rather than a detached copy) so `resume` can hand it straight
+/// back to whatever drives the interpreter's instruction loop above this
+/// native-module boundary, instead of that driver having to fabricate an unrelated
+/// context to keep going with.
+pub struct Resumable<'c> {
+	function_name: String,
+	return_type: Option<ValueType>,
+	context: CallerContext<'c>,
+}
+
+impl<'c> Resumable<'c> {
+	/// Resume the suspended call by supplying its result.
+	///
+	/// `value` is validated against the call's declared return type; a mismatch is
+	/// reported as `Error::Native` rather than silently coerced. Takes a `Cow` so
+	/// callers can pass either an owned or a borrowed `RuntimeValue`. On success,
+	/// hands back the suspended call's own `CallerContext` together with the
+	/// now-validated value — neither is pushed onto the value stack here, since doing
+	/// that is exactly what re-entering the instruction loop above this native-module
+	/// boundary needs to do with them to actually continue the suspended activation.
+	pub fn resume(self, value: Cow<RuntimeValue>) -> Result<(CallerContext<'c>, RuntimeValue), Error> {
+		let value = value.into_owned();
+		check_return_type(&self.function_name, self.return_type, &value)?;
+		Ok((self.context, value))
 	}
 }
 
-/// Create wrapper for env module with given native user functions.
-pub fn env_native_module<'a>(env: Arc<ModuleInstanceInterface>, user_functions: UserFunctions<'a>) -> Result<NativeModuleInstance, Error> {
-	NativeModuleInstance::new(env, user_functions)
+/// Check a resumed call's supplied value against its declared return type, shared by
+/// `Resumable::resume` so the mismatch message only needs writing once.
+fn check_return_type(function_name: &str, expected: Option<ValueType>, value: &RuntimeValue) -> Result<(), Error> {
+	let actual = Some(value.value_type());
+	if actual != expected {
+		return Err(Error::Native(format!(
+			"native function '{}' declared return type {:?}, but was resumed with a {:?} value",
+			function_name, expected, actual,
+		)));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod resume_tests {
+	use super::check_return_type;
+	use elements::ValueType;
+	use interpreter::value::RuntimeValue;
+
+	#[test]
+	fn accepts_a_value_of_the_declared_return_type() {
+		assert!(check_return_type("f", Some(ValueType::I32), &RuntimeValue::I32(1)).is_ok());
+	}
+
+	#[test]
+	fn rejects_a_value_when_no_return_was_declared() {
+		assert!(check_return_type("f", None, &RuntimeValue::I32(1)).is_err());
+	}
+
+	#[test]
+	fn rejects_a_value_of_the_wrong_type() {
+		assert!(check_return_type("f", Some(ValueType::I32), &RuntimeValue::I64(1)).is_err());
+	}
+}
+
+impl<'a> NativeModuleInstance<'a> {
+	/// Like `call_internal_function`, but lets the native function suspend instead of
+	/// completing synchronously, returning a `Resumable` handle — still holding the
+	/// caller's own `CallerContext` — in that case.
+	pub fn call_internal_function_resumable<'c>(&self, mut outer: CallerContext<'c>, index: u32) -> Result<Resumed<'c>, Error> {
+		if index < NATIVE_INDEX_FUNC_MIN {
+			return self.env.call_internal_function(outer, index).map(Resumed::Finished);
+		}
+
+		let descriptor = self.functions
+			.get((index - NATIVE_INDEX_FUNC_MIN) as usize)
+			.ok_or(Error::Native(format!("trying to call native function with index {}", index)))?;
+		let args = RuntimeArgs::pop_from(descriptor.params(), &mut outer)?;
+		match self.executor.write().execute(&descriptor.name(), &mut outer, args) {
+			Err(Error::Suspend(suspended_return_type)) => Ok(Resumed::Suspended(Resumable {
+				function_name: descriptor.name().to_owned(),
+				return_type: suspended_return_type,
+				context: outer,
+			})),
+			other => other.map(Resumed::Finished),
+		}
+	}
+}
+
+/// Create wrapper for env module with given host imports.
+pub fn env_native_module<'a>(env: Arc<ModuleInstanceInterface>, host_imports: HostImports<'a>) -> Result<NativeModuleInstance, Error> {
+	NativeModuleInstance::new(env, host_imports)
+}
+
+// `HostImports` is just the raw, pre-link data (descriptors plus an executor, and
+// bare memory/table/global maps); it has no `ModuleInstanceInterface` to hand out a
+// working `InternalFunctionReference` from. `NativeModuleInstance` is what wraps that
+// data into something callable, so it's the type that can actually resolve all four
+// import kinds, including functions — and the type a module linker should hold onto
+// as `&ImportResolver` for the host module it's linking against.
+impl<'a> ImportResolver for NativeModuleInstance<'a> {
+	fn resolve_func<'b>(&'b self, field: &str, required_type: &FunctionSignature) -> Result<InternalFunctionReference<'b>, Error> {
+		let index = self.by_name.get(field)
+			.ok_or_else(|| Error::Native(format!("trying to import unknown host function '{}'", field)))?;
+		let composite_index = NATIVE_INDEX_FUNC_MIN + *index;
+		let actual_type = self.function_type(ItemIndex::Internal(composite_index))?;
+		if actual_type != *required_type {
+			return Err(Error::Native(format!(
+				"host function '{}' signature mismatch: module expects {:?}, host provides {:?}",
+				field, required_type, actual_type,
+			)));
+		}
+
+		self.function_reference(ItemIndex::Internal(composite_index), None)
+	}
+
+	fn resolve_memory(&self, field: &str, descriptor: &MemoryDescriptor) -> Result<Arc<MemoryInstance>, Error> {
+		let index = self.memories_by_name.get(field)
+			.ok_or_else(|| Error::Native(format!("trying to import unknown host memory '{}'", field)))?;
+		let memory = self.memory(ItemIndex::Internal(NATIVE_INDEX_MEMORY_MIN + *index))?;
+		if memory.current_size() < descriptor.minimum() || !limits_satisfy_maximum(descriptor.maximum(), memory.maximum_size()) {
+			return Err(Error::Native(format!(
+				"host memory '{}' size mismatch: module expects minimum {:?} and maximum {:?}, host provides minimum {:?} and maximum {:?}",
+				field, descriptor.minimum(), descriptor.maximum(), memory.current_size(), memory.maximum_size(),
+			)));
+		}
+
+		Ok(memory)
+	}
+
+	fn resolve_table(&self, field: &str, descriptor: &TableDescriptor) -> Result<Arc<TableInstance>, Error> {
+		let index = self.tables_by_name.get(field)
+			.ok_or_else(|| Error::Native(format!("trying to import unknown host table '{}'", field)))?;
+		let table = self.table(ItemIndex::Internal(NATIVE_INDEX_TABLE_MIN + *index))?;
+		if table.current_size() < descriptor.minimum() || !limits_satisfy_maximum(descriptor.maximum(), table.maximum_size()) {
+			return Err(Error::Native(format!(
+				"host table '{}' size mismatch: module expects minimum {:?} and maximum {:?}, host provides minimum {:?} and maximum {:?}",
+				field, descriptor.minimum(), descriptor.maximum(), table.current_size(), table.maximum_size(),
+			)));
+		}
+
+		Ok(table)
+	}
+
+	fn resolve_global(&self, field: &str, descriptor: &GlobalDescriptor) -> Result<Arc<VariableInstance>, Error> {
+		let index = self.globals_by_name.get(field)
+			.ok_or_else(|| Error::Native(format!("trying to import unknown host global '{}'", field)))?;
+		let global = self.global(ItemIndex::Internal(NATIVE_INDEX_GLOBAL_MIN + *index), None)?;
+		if global.variable_type() != descriptor.content_type() || global.is_mutable() != descriptor.is_mutable() {
+			return Err(Error::Native(format!(
+				"host global '{}' type mismatch: module expects {:?} (mutable: {}), host provides {:?} (mutable: {})",
+				field, descriptor.content_type(), descriptor.is_mutable(), global.variable_type(), global.is_mutable(),
+			)));
+		}
+
+		Ok(global)
+	}
+}
+
+/// A host-provided memory/table's actual maximum satisfies a module's declared
+/// maximum as long as the module doesn't require one that's tighter than what the
+/// host committed to: no declared maximum accepts anything, but a declared maximum
+/// requires the host to have committed to a maximum of its own that is no larger.
+fn limits_satisfy_maximum(required_maximum: Option<u32>, actual_maximum: Option<u32>) -> bool {
+	match required_maximum {
+		None => true,
+		Some(required) => actual_maximum.map(|actual| actual <= required).unwrap_or(false),
+	}
+}
+
+#[cfg(test)]
+mod limits_satisfy_maximum_tests {
+	use super::limits_satisfy_maximum;
+
+	#[test]
+	fn no_declared_maximum_accepts_any_host_maximum() {
+		assert!(limits_satisfy_maximum(None, None));
+		assert!(limits_satisfy_maximum(None, Some(1)));
+	}
+
+	#[test]
+	fn declared_maximum_requires_a_host_maximum_no_larger() {
+		assert!(limits_satisfy_maximum(Some(10), Some(10)));
+		assert!(limits_satisfy_maximum(Some(10), Some(5)));
+		assert!(!limits_satisfy_maximum(Some(10), Some(11)));
+	}
+
+	#[test]
+	fn declared_maximum_rejects_a_host_with_no_maximum_at_all() {
+		assert!(!limits_satisfy_maximum(Some(10), None));
+	}
 }
 
 impl<'a> PartialEq for UserFunctionDescriptor {
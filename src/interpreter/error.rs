@@ -0,0 +1,43 @@
+use std::fmt;
+use elements::ValueType;
+
+/// Interpreter error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+	/// Module validation error.
+	Validation(String),
+	/// Trap.
+	Trap(String),
+	/// Native module error.
+	Native(String),
+	/// Global-related error.
+	Global(String),
+	/// Memory-related error.
+	Memory(String),
+	/// Table-related error.
+	Table(String),
+	/// Function-related error.
+	Function(String),
+	/// Program-related error.
+	Program(String),
+	/// A native function call suspended instead of returning a result, carrying the
+	/// declared return type of the suspended call so the embedder can validate the
+	/// value it eventually resumes with against it.
+	Suspend(Option<ValueType>),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Validation(ref s) => write!(f, "Validation error: {}", s),
+			Error::Trap(ref s) => write!(f, "Trap: {}", s),
+			Error::Native(ref s) => write!(f, "Native error: {}", s),
+			Error::Global(ref s) => write!(f, "Global error: {}", s),
+			Error::Memory(ref s) => write!(f, "Memory error: {}", s),
+			Error::Table(ref s) => write!(f, "Table error: {}", s),
+			Error::Function(ref s) => write!(f, "Function error: {}", s),
+			Error::Program(ref s) => write!(f, "Program error: {}", s),
+			Error::Suspend(ref return_type) => write!(f, "Call suspended, expecting resume with a {:?} value", return_type),
+		}
+	}
+}
@@ -0,0 +1,4 @@
+mod error;
+pub mod env_native;
+
+pub use self::error::Error;